@@ -2,8 +2,13 @@ use petgraph::algo::astar;
 use petgraph::graph::NodeIndex;
 use petgraph::graph::UnGraph;
 use petgraph::visit::Bfs;
+use petgraph::visit::EdgeRef;
 use petgraph::{Graph, Undirected};
 
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs::{read_to_string, write};
 use std::ops::Add;
 
@@ -12,11 +17,39 @@ use serde_json::json;
 
 use clap::{App, Arg};
 
+use csv::Reader;
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
 #[macro_use]
 extern crate svgmacro;
 
 type Undirected2DCoordinateGraph = Graph<Coordinate2D, i32, Undirected, u32>;
 
+/// Cost of an orthogonal (N/S/E/W) move on a `Connectivity::Eight` grid, per
+/// unit of terrain cost, scaled up so the diagonal move cost below can stay
+/// an integer approximation of `sqrt(2)`. On a `Connectivity::Four` grid an
+/// orthogonal move costs the bare terrain cost.
+const ORTHOGONAL_COST: i32 = 10;
+/// Cost of a diagonal grid move, `ORTHOGONAL_COST * sqrt(2)` rounded.
+const DIAGONAL_COST: i32 = 14;
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, Default)]
+enum Connectivity {
+    #[default]
+    Four,
+    Eight,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, Default)]
+enum Algorithm {
+    #[default]
+    AStar,
+    Dijkstra,
+    Bfs,
+    BidirectionalAStar,
+}
+
 fn main() -> std::io::Result<()> {
     let matches = App::new("My Test Program")
         .version("0.1.0")
@@ -32,7 +65,7 @@ fn main() -> std::io::Result<()> {
         )
         .arg(
             Arg::with_name("json")
-                .required_unless("svg")
+                .required_unless_one(&["svg", "serve"])
                 .short("j")
                 .long("json")
                 .takes_value(true)
@@ -40,20 +73,158 @@ fn main() -> std::io::Result<()> {
         )
         .arg(
             Arg::with_name("svg")
-                .required_unless("json")
+                .required_unless_one(&["json", "serve"])
                 .short("s")
                 .long("svg")
                 .takes_value(true)
                 .help("The svg file to write results to"),
         )
+        .arg(
+            Arg::with_name("mode")
+                .short("m")
+                .long("mode")
+                .takes_value(true)
+                .possible_values(&["grid", "geo"])
+                .default_value("grid")
+                .help("Whether to route over an implicit grid or a geographic node network"),
+        )
+        .arg(
+            Arg::with_name("nodes")
+                .long("nodes")
+                .takes_value(true)
+                .required_if("mode", "geo")
+                .help("CSV of geographic nodes (id, name, lat, lon), for --mode geo"),
+        )
+        .arg(
+            Arg::with_name("edges")
+                .long("edges")
+                .takes_value(true)
+                .required_if("mode", "geo")
+                .help("CSV of geographic edges (from_id, to_id), for --mode geo"),
+        )
+        .arg(
+            Arg::with_name("serve")
+                .long("serve")
+                .takes_value(true)
+                .value_name("PORT")
+                .help("Run as an HTTP routing service on PORT instead of writing files once"),
+        )
+        .arg(
+            Arg::with_name("algorithm")
+                .short("a")
+                .long("algorithm")
+                .takes_value(true)
+                .possible_values(&["astar", "dijkstra", "bfs", "bidirectional"])
+                .default_value("astar")
+                .help("Search algorithm to use for --mode grid"),
+        )
+        .arg(
+            Arg::with_name("diff")
+                .long("diff")
+                .takes_value(true)
+                .conflicts_with("serve")
+                .help("A second --mode grid input configuration to diff against"),
+        )
         .get_matches();
 
     let input_str = matches.value_of("input").unwrap();
     let str = read_to_string(input_str)?;
+    let config = str_to_config(str)?;
+    let algorithm = match matches.value_of("algorithm") {
+        Some("dijkstra") => Algorithm::Dijkstra,
+        Some("bfs") => Algorithm::Bfs,
+        Some("bidirectional") => Algorithm::BidirectionalAStar,
+        _ => Algorithm::AStar,
+    };
+
+    if let Some(diff_input) = matches.value_of("diff") {
+        if matches.value_of("mode") == Some("geo") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--diff only supports --mode grid, not --mode geo",
+            ));
+        }
+
+        let other_config = str_to_config(read_to_string(diff_input)?)?;
+        let g_before = make_graph(&config);
+        let g_after = make_graph(&other_config);
+        let path_before = find_path(
+            &g_before,
+            config.start,
+            config.goal,
+            config.connectivity,
+            algorithm,
+        );
+        let path_after = find_path(
+            &g_after,
+            other_config.start,
+            other_config.goal,
+            other_config.connectivity,
+            algorithm,
+        );
+        let diff = diff_graphs(&g_before, &g_after);
+        let alignment = align_paths(
+            &path_coordinates(&g_before, &path_before),
+            &path_coordinates(&g_after, &path_after),
+        );
+
+        if let Some(json_path) = matches.value_of("json") {
+            write(json_path, graph_to_json_diff(&g_after, &diff, &alignment))?;
+        }
+
+        if let Some(svg_path) = matches.value_of("svg") {
+            let svg = graph_to_svg_diff(&g_before, &g_after, config.scale, &diff, &alignment);
+            write(svg_path, svg)?;
+        }
+
+        return Ok(());
+    }
+
+    if matches.value_of("mode") == Some("geo") {
+        let nodes = load_geo_nodes(matches.value_of("nodes").unwrap());
+        let edges = load_geo_edges(matches.value_of("edges").unwrap());
+        let g = make_geo_graph(&nodes, &edges);
+
+        if let Some(port) = matches.value_of("serve") {
+            let port: u16 = port.parse().expect("PORT must be a valid port number");
+            return serve_geo(g, config, port);
+        }
+
+        let start_id = config
+            .start_id
+            .as_deref()
+            .expect("start_id required in geo mode");
+        let goal_id = config
+            .goal_id
+            .as_deref()
+            .expect("goal_id required in geo mode");
+        let path = find_geo_path(&g, start_id, goal_id);
+
+        if let Some(json_path) = matches.value_of("json") {
+            write(json_path, graph_to_json_geo(&g, &path))?;
+        }
+
+        if let Some(svg_path) = matches.value_of("svg") {
+            write(svg_path, graph_to_svg_geo(&g, config.scale, &path))?;
+        }
+
+        return Ok(());
+    }
 
-    let config = str_to_config(str);
     let g = make_graph(&config);
-    let path = find_path(&g, config.start, config.goal);
+
+    if let Some(port) = matches.value_of("serve") {
+        let port: u16 = port.parse().expect("PORT must be a valid port number");
+        return serve_grid(g, config, algorithm, port);
+    }
+
+    let path = find_path(
+        &g,
+        config.start,
+        config.goal,
+        config.connectivity,
+        algorithm,
+    );
 
     if let Some(json_path) = matches.value_of("json") {
         let json = graph_to_json(&g, &path);
@@ -68,7 +239,9 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Debug, PartialOrd, Default)]
+#[derive(
+    Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Debug, PartialOrd, Ord, Hash, Default,
+)]
 struct Coordinate2D {
     x: i16,
     y: i16,
@@ -76,7 +249,16 @@ struct Coordinate2D {
 
 impl Coordinate2D {
     fn distance(self, other: Self) -> f32 {
-        (((self.y - other.y).pow(2) + (self.x - self.y).pow(2)) as f32).sqrt()
+        (((self.y - other.y).pow(2) + (self.x - other.x).pow(2)) as f32).sqrt()
+    }
+
+    /// Integer-scaled octile distance, the admissible heuristic for an
+    /// 8-connected grid where orthogonal moves cost `ORTHOGONAL_COST` and
+    /// diagonal moves cost `DIAGONAL_COST`.
+    fn octile_distance(self, other: Self) -> i32 {
+        let dx = (self.x - other.x).unsigned_abs() as i32;
+        let dy = (self.y - other.y).unsigned_abs() as i32;
+        ORTHOGONAL_COST * (dx + dy) + (DIAGONAL_COST - 2 * ORTHOGONAL_COST) * dx.min(dy)
     }
 }
 
@@ -98,6 +280,54 @@ struct Config {
     height: i16,
     width: i16,
     scale: i16,
+    /// Per-cell traversal cost, indexed `costs[y][x]`. Cells not covered by
+    /// this grid (or when it is omitted entirely) default to a cost of `1`.
+    #[serde(default)]
+    costs: Option<Vec<Vec<i32>>>,
+    /// Coordinates that have no node at all, i.e. walls the planner must
+    /// route around instead of through.
+    #[serde(default)]
+    blocked: Vec<Coordinate2D>,
+    /// Whether grid cells are wired with 4 (orthogonal only) or 8
+    /// (orthogonal + diagonal) neighbors.
+    #[serde(default)]
+    connectivity: Connectivity,
+    /// Id of the start node, used instead of `start` in `--mode geo`.
+    #[serde(default)]
+    start_id: Option<String>,
+    /// Id of the goal node, used instead of `goal` in `--mode geo`.
+    #[serde(default)]
+    goal_id: Option<String>,
+}
+
+impl Config {
+    fn is_blocked(&self, coord: Coordinate2D) -> bool {
+        self.blocked.contains(&coord)
+    }
+
+    fn cost_at(&self, coord: Coordinate2D) -> i32 {
+        self.costs
+            .as_ref()
+            .and_then(|rows| rows.get(coord.y as usize))
+            .and_then(|row| row.get(coord.x as usize))
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Rejects non-positive terrain costs. `find_path` hands these straight
+    /// to petgraph's `astar`, which assumes non-negative edge weights and
+    /// simply never terminates if that assumption is violated, so a bad
+    /// `costs` grid must be caught here rather than surfacing as a hang.
+    fn validate(&self) -> std::io::Result<()> {
+        let has_non_positive_cost = self.costs.iter().flatten().flatten().any(|&cost| cost < 1);
+        if has_non_positive_cost {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "costs must all be >= 1",
+            ));
+        }
+        Ok(())
+    }
 }
 
 fn make_graph(config: &Config) -> Undirected2DCoordinateGraph {
@@ -105,31 +335,236 @@ fn make_graph(config: &Config) -> Undirected2DCoordinateGraph {
     let field_width = config.width;
     let node_count = field_height * field_width;
     let mut g = UnGraph::with_capacity(node_count as usize, (node_count * 2) as usize);
-    let mut last_node_line = vec![None; field_width as usize];
+    let mut prev_row = vec![None; field_width as usize];
+    let diagonal = matches!(config.connectivity, Connectivity::Eight);
 
     let mut y = 0;
     while y < field_height {
+        let mut current_row = vec![None; field_width as usize];
         let mut x = 0;
         let mut last_node = None;
         while x < field_width {
-            let current_node = g.add_node(Coordinate2D { x, y });
+            let coord = Coordinate2D { x, y };
+            if config.is_blocked(coord) {
+                last_node = None;
+                x += 1;
+                continue;
+            }
+
+            let current_node = g.add_node(coord);
+            let orthogonal_weight = if diagonal {
+                config.cost_at(coord) * ORTHOGONAL_COST
+            } else {
+                config.cost_at(coord)
+            };
+            let diagonal_weight = config.cost_at(coord) * DIAGONAL_COST;
 
-            if last_node_line[x as usize].is_some() {
-                g.add_edge(current_node, last_node_line[x as usize].unwrap(), 1);
+            if let Some(above) = prev_row[x as usize] {
+                g.add_edge(current_node, above, orthogonal_weight);
             }
             if let Some(last_node_unwrapped) = last_node {
-                g.add_edge(current_node, last_node_unwrapped, 1);
+                g.add_edge(current_node, last_node_unwrapped, orthogonal_weight);
+            }
+            if diagonal {
+                if x > 0 {
+                    if let Some(above_left) = prev_row[(x - 1) as usize] {
+                        g.add_edge(current_node, above_left, diagonal_weight);
+                    }
+                }
+                if x + 1 < field_width {
+                    if let Some(above_right) = prev_row[(x + 1) as usize] {
+                        g.add_edge(current_node, above_right, diagonal_weight);
+                    }
+                }
             }
             last_node = Some(current_node);
-            last_node_line[x as usize] = Some(current_node);
+            current_row[x as usize] = Some(current_node);
             x += 1;
         }
+        prev_row = current_row;
         y += 1;
     }
 
     g
 }
 
+/// Radius of the Earth used for haversine distances, in kilometers.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+type GeoRouteGraph = Graph<GeoCoordinate, f64, Undirected, u32>;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct GeoCoordinate {
+    id: String,
+    name: String,
+    lat: f64,
+    lon: f64,
+}
+
+impl GeoCoordinate {
+    /// Great-circle distance to `other`, in kilometers, via the haversine formula.
+    fn haversine_distance(&self, other: &Self) -> f64 {
+        let phi1 = self.lat.to_radians();
+        let phi2 = other.lat.to_radians();
+        let delta_phi = (other.lat - self.lat).to_radians();
+        let delta_lambda = (other.lon - self.lon).to_radians();
+
+        let a = (delta_phi / 2.0).sin().powi(2)
+            + phi1.cos() * phi2.cos() * (delta_lambda / 2.0).sin().powi(2);
+        let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+
+        EARTH_RADIUS_KM * c
+    }
+}
+
+#[derive(Deserialize)]
+struct GeoEdgeRecord {
+    from_id: String,
+    to_id: String,
+}
+
+fn load_geo_nodes(path: &str) -> Vec<GeoCoordinate> {
+    let mut reader = Reader::from_path(path).expect("failed to open nodes CSV");
+    reader
+        .deserialize()
+        .map(|record| record.expect("malformed node record"))
+        .collect()
+}
+
+fn load_geo_edges(path: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_path(path).expect("failed to open edges CSV");
+    reader
+        .deserialize()
+        .map(|record: Result<GeoEdgeRecord, _>| {
+            let record = record.expect("malformed edge record");
+            (record.from_id, record.to_id)
+        })
+        .collect()
+}
+
+fn make_geo_graph(nodes: &[GeoCoordinate], edges: &[(String, String)]) -> GeoRouteGraph {
+    let mut g = GeoRouteGraph::with_capacity(nodes.len(), edges.len());
+    let mut index_by_id = HashMap::new();
+    for node in nodes {
+        let idx = g.add_node(node.clone());
+        index_by_id.insert(node.id.clone(), idx);
+    }
+    for (from_id, to_id) in edges {
+        if let (Some(&from_idx), Some(&to_idx)) = (index_by_id.get(from_id), index_by_id.get(to_id))
+        {
+            let weight = g[from_idx].haversine_distance(&g[to_idx]);
+            g.add_edge(from_idx, to_idx, weight);
+        }
+    }
+    g
+}
+
+fn find_geo_path(
+    g: &GeoRouteGraph,
+    start_id: &str,
+    goal_id: &str,
+) -> Option<(f64, Vec<NodeIndex>)> {
+    let start_index = g.node_indices().find(|x| g[*x].id == start_id)?;
+    let goal_index = g.node_indices().find(|x| g[*x].id == goal_id)?;
+    let goal_coord = g[goal_index].clone();
+
+    astar(
+        g,
+        start_index,
+        |x: NodeIndex| x == goal_index,
+        |e| *e.weight(),
+        |x| g[x].haversine_distance(&goal_coord),
+    )
+}
+
+fn graph_to_json_geo(g: &GeoRouteGraph, path: &Option<(f64, Vec<NodeIndex>)>) -> String {
+    let nodes: Vec<GeoCoordinate> = g.node_indices().map(|idx| g[idx].clone()).collect();
+    let mut edges = Vec::new();
+    for e in g.edge_indices() {
+        if let Some((a, b)) = g.edge_endpoints(e) {
+            edges.push((g[a].id.clone(), g[b].id.clone()));
+        }
+    }
+
+    let out = if let Some(path) = path {
+        json!({
+            "nodes": nodes,
+            "edges": edges,
+            "path": path.1.iter().map(|x| g[*x].id.clone()).collect::<Vec<String>>(),
+            "distance_km": path.0,
+        })
+    } else {
+        json!({
+            "nodes": nodes,
+            "edges": edges,
+        })
+    };
+    serde_json::to_string(&out).unwrap()
+}
+
+fn graph_to_svg_geo(g: &GeoRouteGraph, scale: i16, path: &Option<(f64, Vec<NodeIndex>)>) -> String {
+    use std::fmt::Write;
+
+    let (min_lat, max_lat, min_lon, max_lon) = g.node_indices().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(lo_lat, hi_lat, lo_lon, hi_lon), idx| {
+            let coord = &g[idx];
+            (
+                lo_lat.min(coord.lat),
+                hi_lat.max(coord.lat),
+                lo_lon.min(coord.lon),
+                hi_lon.max(coord.lon),
+            )
+        },
+    );
+
+    let scale = scale as f64;
+    let width = ((max_lon - min_lon) * scale).max(scale);
+    let height = ((max_lat - min_lat) * scale).max(scale);
+    let project = |coord: &GeoCoordinate| -> (f64, f64) {
+        (
+            (coord.lon - min_lon) * scale + scale / 2.0,
+            (max_lat - coord.lat) * scale + scale / 2.0,
+        )
+    };
+
+    let start_index = path.as_ref().and_then(|p| p.1.first().copied());
+    let goal_index = path.as_ref().and_then(|p| p.1.last().copied());
+
+    let mut out = String::new();
+    svg!(&mut out,
+        svg(width={width + scale} height={height + scale} xmlns="http://www.w3.org/2000/svg") [
+            @ for e in g.edge_indices() {
+                if let Some((a, b)) = g.edge_endpoints(e) {
+                    let (x1, y1) = project(&g[a]);
+                    let (x2, y2) = project(&g[b]);
+                    if let Some(path) = path {
+                        if path.1.contains(&a) && path.1.contains(&b) {
+                            svg!(&mut out, line(x1={x1} y1={y1} x2={x2} y2={y2} style="stroke:rgb(0, 255, 0); stroke-width:2"));
+                        } else {
+                            svg!(&mut out, line(x1={x1} y1={y1} x2={x2} y2={y2} style="stroke:rgb(0, 0, 0); stroke-width:1"));
+                        }
+                    } else {
+                        svg!(&mut out, line(x1={x1} y1={y1} x2={x2} y2={y2} style="stroke:rgb(0, 0, 0); stroke-width:1"));
+                    }
+                }
+            };
+            @ for idx in g.node_indices() {
+                let coord = &g[idx];
+                let (x, y) = project(coord);
+                if Some(idx) == start_index {
+                    svg!(&mut out, circle(cx={x} cy={y} r={scale/10.0} style="stroke: rgb(255, 0, 0); fill: rgb(255, 0, 0)"));
+                } else if Some(idx) == goal_index {
+                    svg!(&mut out, circle(cx={x} cy={y} r={scale/10.0} style="stroke: rgb(0, 0, 255); fill: rgb(0, 0, 255)"));
+                } else {
+                    svg!(&mut out, circle(cx={x} cy={y} r={scale/15.0}));
+                }
+            };
+    ]);
+
+    out
+}
+
 fn graph_to_svg(
     g: &Undirected2DCoordinateGraph,
     scale: i16,
@@ -145,7 +580,7 @@ fn graph_to_svg(
     let mut out = String::new();
     let mut bfs = Bfs::new(&g, g.node_indices().next().unwrap());
 
-    SVG!(&mut out,
+    svg!(&mut out,
         svg(width={field_width*scale + scale} height={field_height*scale + scale} xmlns="http://www.w3.org/2000/svg") [
             @ while let Some(nx) = bfs.next(&g) {
                 let coord = g[nx];
@@ -154,20 +589,20 @@ fn graph_to_svg(
                     let ncoord = g[neighbor];
                     if let Some(path) = path {
                         if path.1.contains(&neighbor) && path.1.contains(&nx) {
-                            SVG!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 255, 0); stroke-width:2"));
+                            svg!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 255, 0); stroke-width:2"));
                         } else {
-                            SVG!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 0, 0); stroke-width:1"));
+                            svg!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 0, 0); stroke-width:1"));
                         }
                     } else {
-                        SVG!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 0, 0); stroke-width:1"));
+                        svg!(&mut out, line(x1={offset+scale*coord.x} y1={offset+scale*coord.y} x2={offset+scale*ncoord.x} y2={offset+scale*ncoord.y} style="stroke:rgb(0, 0, 0); stroke-width:1"));
                     }
                 }
                 if coord == start {
-                    SVG!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/10} style="stroke: rgb(255, 0, 0); fill: rgb(255, 0, 0)"));
+                    svg!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/10} style="stroke: rgb(255, 0, 0); fill: rgb(255, 0, 0)"));
                 } else if coord == goal {
-                    SVG!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/10} style="stroke: rgb(0, 0, 255); fill: rgb(0, 0, 255)"));
+                    svg!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/10} style="stroke: rgb(0, 0, 255); fill: rgb(0, 0, 255)"));
                 } else {
-                    SVG!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/15}));
+                    svg!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/15}));
                 }
             };
     ]);
@@ -203,23 +638,843 @@ fn graph_to_json(g: &Undirected2DCoordinateGraph, path: &Option<(i32, Vec<NodeIn
     serde_json::to_string(&out).unwrap()
 }
 
+fn path_coordinates(
+    g: &Undirected2DCoordinateGraph,
+    path: &Option<(i32, Vec<NodeIndex>)>,
+) -> Vec<Coordinate2D> {
+    path.as_ref()
+        .map(|(_, nodes)| nodes.iter().map(|x| g[*x]).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+enum DiffStatus {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// One node of a Levenshtein-aligned path sequence, tagged with whether it
+/// only appears before the edit, only after, or in both.
+#[derive(Clone, Debug, Serialize)]
+struct PathAlignmentEntry {
+    status: DiffStatus,
+    node: Coordinate2D,
+}
+
+/// Node- and edge-level differences between two grid graphs, matched by
+/// `Coordinate2D` identity.
+#[derive(Debug, Serialize)]
+struct GraphDiff {
+    added_nodes: Vec<Coordinate2D>,
+    removed_nodes: Vec<Coordinate2D>,
+    added_edges: Vec<(Coordinate2D, Coordinate2D)>,
+    removed_edges: Vec<(Coordinate2D, Coordinate2D)>,
+}
+
+fn canonical_edges(g: &Undirected2DCoordinateGraph) -> HashSet<(Coordinate2D, Coordinate2D)> {
+    g.edge_indices()
+        .filter_map(|e| g.edge_endpoints(e))
+        .map(|(a, b)| {
+            let (a, b) = (g[a], g[b]);
+            if a <= b {
+                (a, b)
+            } else {
+                (b, a)
+            }
+        })
+        .collect()
+}
+
+fn diff_graphs(
+    before: &Undirected2DCoordinateGraph,
+    after: &Undirected2DCoordinateGraph,
+) -> GraphDiff {
+    let nodes_before: HashSet<Coordinate2D> = before.node_indices().map(|i| before[i]).collect();
+    let nodes_after: HashSet<Coordinate2D> = after.node_indices().map(|i| after[i]).collect();
+    let edges_before = canonical_edges(before);
+    let edges_after = canonical_edges(after);
+
+    GraphDiff {
+        added_nodes: nodes_after.difference(&nodes_before).copied().collect(),
+        removed_nodes: nodes_before.difference(&nodes_after).copied().collect(),
+        added_edges: edges_after.difference(&edges_before).copied().collect(),
+        removed_edges: edges_before.difference(&edges_after).copied().collect(),
+    }
+}
+
+/// Aligns two path sequences with a standard Levenshtein edit-distance
+/// traceback, classifying each resulting node as unchanged (present in both,
+/// in order), added (only in `after`), or removed (only in `before`).
+fn align_paths(before: &[Coordinate2D], after: &[Coordinate2D]) -> Vec<PathAlignmentEntry> {
+    let n = before.len();
+    let m = after.len();
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in dp[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            dp[i][j] = if before[i - 1] == after[j - 1] {
+                dp[i - 1][j - 1]
+            } else {
+                1 + dp[i - 1][j].min(dp[i][j - 1]).min(dp[i - 1][j - 1])
+            };
+        }
+    }
+
+    let mut entries = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && before[i - 1] == after[j - 1] && dp[i][j] == dp[i - 1][j - 1] {
+            entries.push(PathAlignmentEntry {
+                status: DiffStatus::Unchanged,
+                node: before[i - 1],
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && j > 0 && dp[i][j] == dp[i - 1][j - 1] + 1 {
+            entries.push(PathAlignmentEntry {
+                status: DiffStatus::Added,
+                node: after[j - 1],
+            });
+            entries.push(PathAlignmentEntry {
+                status: DiffStatus::Removed,
+                node: before[i - 1],
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && (j == 0 || dp[i][j] == dp[i - 1][j] + 1) {
+            entries.push(PathAlignmentEntry {
+                status: DiffStatus::Removed,
+                node: before[i - 1],
+            });
+            i -= 1;
+        } else {
+            entries.push(PathAlignmentEntry {
+                status: DiffStatus::Added,
+                node: after[j - 1],
+            });
+            j -= 1;
+        }
+    }
+    entries.reverse();
+    entries
+}
+
+fn graph_to_json_diff(
+    g_after: &Undirected2DCoordinateGraph,
+    diff: &GraphDiff,
+    alignment: &[PathAlignmentEntry],
+) -> String {
+    let nodes: Vec<Coordinate2D> = g_after.node_indices().map(|i| g_after[i]).collect();
+    let edges: Vec<(Coordinate2D, Coordinate2D)> = canonical_edges(g_after).into_iter().collect();
+
+    let out = json!({
+        "nodes": nodes,
+        "edges": edges,
+        "diff": {
+            "added_nodes": diff.added_nodes,
+            "removed_nodes": diff.removed_nodes,
+            "added_edges": diff.added_edges,
+            "removed_edges": diff.removed_edges,
+            "path_alignment": alignment,
+        },
+    });
+    serde_json::to_string(&out).unwrap()
+}
+
+fn diff_segment_color(status: DiffStatus) -> &'static str {
+    match status {
+        DiffStatus::Unchanged => "rgb(0, 255, 0)",
+        DiffStatus::Added => "rgb(0, 128, 255)",
+        DiffStatus::Removed => "rgb(255, 0, 0)",
+    }
+}
+
+fn graph_to_svg_diff(
+    g_before: &Undirected2DCoordinateGraph,
+    g_after: &Undirected2DCoordinateGraph,
+    scale: i16,
+    diff: &GraphDiff,
+    alignment: &[PathAlignmentEntry],
+) -> String {
+    use std::fmt::Write;
+
+    let offset = scale / 2;
+    let max_x = g_before
+        .node_indices()
+        .map(|i| g_before[i].x)
+        .chain(g_after.node_indices().map(|i| g_after[i].x))
+        .max()
+        .unwrap_or(0);
+    let max_y = g_before
+        .node_indices()
+        .map(|i| g_before[i].y)
+        .chain(g_after.node_indices().map(|i| g_after[i].y))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    let added_nodes: HashSet<_> = diff.added_nodes.iter().copied().collect();
+    let removed_nodes: HashSet<_> = diff.removed_nodes.iter().copied().collect();
+    let added_edges: HashSet<_> = diff.added_edges.iter().copied().collect();
+    let removed_edges: HashSet<_> = diff.removed_edges.iter().copied().collect();
+    let all_edges: HashSet<_> = canonical_edges(g_before)
+        .into_iter()
+        .chain(canonical_edges(g_after))
+        .collect();
+    let all_nodes: HashSet<Coordinate2D> = g_before
+        .node_indices()
+        .map(|i| g_before[i])
+        .chain(g_after.node_indices().map(|i| g_after[i]))
+        .collect();
+
+    svg!(&mut out,
+        svg(width={max_x*scale + scale*2} height={max_y*scale + scale*2} xmlns="http://www.w3.org/2000/svg") [
+            @ for (a, b) in all_edges.iter() {
+                let color = if removed_edges.contains(&(*a, *b)) {
+                    "rgb(255, 0, 0)"
+                } else if added_edges.contains(&(*a, *b)) {
+                    "rgb(0, 128, 255)"
+                } else {
+                    "rgb(0, 0, 0)"
+                };
+                svg!(&mut out, line(x1={offset+scale*a.x} y1={offset+scale*a.y} x2={offset+scale*b.x} y2={offset+scale*b.y} style={format!("stroke:{}; stroke-width:1", color)}));
+            };
+            @ for pair in alignment.windows(2) {
+                let from = &pair[0];
+                let to = &pair[1];
+                let canonical = if from.node <= to.node {
+                    (from.node, to.node)
+                } else {
+                    (to.node, from.node)
+                };
+                if all_edges.contains(&canonical) {
+                    let color = diff_segment_color(to.status);
+                    svg!(&mut out, line(x1={offset+scale*from.node.x} y1={offset+scale*from.node.y} x2={offset+scale*to.node.x} y2={offset+scale*to.node.y} style={format!("stroke:{}; stroke-width:3", color)}));
+                }
+            };
+            @ for coord in all_nodes.iter() {
+                let status = if removed_nodes.contains(coord) {
+                    DiffStatus::Removed
+                } else if added_nodes.contains(coord) {
+                    DiffStatus::Added
+                } else {
+                    DiffStatus::Unchanged
+                };
+                let color = diff_segment_color(status);
+                svg!(&mut out, circle(cx={offset+scale*coord.x} cy={offset+scale*coord.y} r={scale/15} style={format!("stroke:{}; fill:{}", color, color)}));
+            };
+    ]);
+
+    out
+}
+
+fn grid_heuristic(connectivity: Connectivity, a: Coordinate2D, b: Coordinate2D) -> i32 {
+    match connectivity {
+        Connectivity::Four => a.distance(b).floor() as i32,
+        Connectivity::Eight => a.octile_distance(b),
+    }
+}
+
 fn find_path(
     g: &Undirected2DCoordinateGraph,
     start: Coordinate2D,
     end: Coordinate2D,
+    connectivity: Connectivity,
+    algorithm: Algorithm,
 ) -> Option<(i32, Vec<NodeIndex>)> {
-    if let Some(start_index) = g.node_indices().find(|x| g[*x] == start) {
-        return astar(
+    let start_index = g.node_indices().find(|x| g[*x] == start)?;
+    let goal_index = g.node_indices().find(|x| g[*x] == end)?;
+
+    match algorithm {
+        Algorithm::AStar => astar(
             g,
             start_index,
-            |x: NodeIndex| g[x] == end,
-            |_x| 1,
-            |x| end.distance(g[x]).floor() as i32,
+            |x| x == goal_index,
+            |e| *e.weight(),
+            |x| grid_heuristic(connectivity, end, g[x]),
+        ),
+        // Uniform-cost Dijkstra is A* with a heuristic of zero.
+        Algorithm::Dijkstra => astar(g, start_index, |x| x == goal_index, |e| *e.weight(), |_| 0),
+        // Unweighted shortest hops is Dijkstra where every edge costs 1.
+        Algorithm::Bfs => astar(g, start_index, |x| x == goal_index, |_| 1, |_| 0),
+        Algorithm::BidirectionalAStar => bidirectional_astar(g, start_index, goal_index, |a, b| {
+            grid_heuristic(connectivity, g[a], g[b])
+        }),
+    }
+}
+
+/// Bidirectional A*: runs a forward frontier from `start` and a backward
+/// frontier from `goal` simultaneously, each a standard A* search guided by
+/// `heuristic` toward the opposite endpoint. Once a node has been settled by
+/// both frontiers we have a candidate meeting point; we keep searching until
+/// neither frontier's best remaining estimate can beat the best meeting node
+/// found so far, then stitch the two half-paths together.
+fn bidirectional_astar(
+    g: &Undirected2DCoordinateGraph,
+    start: NodeIndex,
+    goal: NodeIndex,
+    heuristic: impl Fn(NodeIndex, NodeIndex) -> i32,
+) -> Option<(i32, Vec<NodeIndex>)> {
+    let mut g_fwd = HashMap::new();
+    let mut g_bwd = HashMap::new();
+    let mut came_from_fwd = HashMap::new();
+    let mut came_from_bwd = HashMap::new();
+    let mut closed_fwd = HashSet::new();
+    let mut closed_bwd = HashSet::new();
+    let mut open_fwd = BinaryHeap::new();
+    let mut open_bwd = BinaryHeap::new();
+
+    g_fwd.insert(start, 0);
+    g_bwd.insert(goal, 0);
+    open_fwd.push(Reverse((heuristic(start, goal), start)));
+    open_bwd.push(Reverse((heuristic(goal, start), goal)));
+
+    let mut best: Option<(i32, NodeIndex)> = None;
+
+    while !open_fwd.is_empty() && !open_bwd.is_empty() {
+        if let Some(min_fwd) = open_fwd.peek().map(|Reverse((f, _))| *f) {
+            if let Some(min_bwd) = open_bwd.peek().map(|Reverse((f, _))| *f) {
+                if let Some((best_cost, _)) = best {
+                    if min_fwd >= best_cost && min_bwd >= best_cost {
+                        break;
+                    }
+                }
+            }
+        }
+
+        expand_frontier(
+            g,
+            goal,
+            &mut open_fwd,
+            &mut closed_fwd,
+            &mut g_fwd,
+            &mut came_from_fwd,
+            &closed_bwd,
+            &g_bwd,
+            &mut best,
+            &heuristic,
+        );
+        expand_frontier(
+            g,
+            start,
+            &mut open_bwd,
+            &mut closed_bwd,
+            &mut g_bwd,
+            &mut came_from_bwd,
+            &closed_fwd,
+            &g_fwd,
+            &mut best,
+            &|a, b| heuristic(b, a),
         );
     }
-    None
+
+    let (cost, meeting) = best?;
+
+    let mut forward_path = vec![meeting];
+    let mut node = meeting;
+    while let Some(&prev) = came_from_fwd.get(&node) {
+        forward_path.push(prev);
+        node = prev;
+    }
+    forward_path.reverse();
+
+    let mut node = meeting;
+    while let Some(&next) = came_from_bwd.get(&node) {
+        forward_path.push(next);
+        node = next;
+    }
+
+    Some((cost, forward_path))
+}
+
+/// Expands the best open node of one A* frontier by one step, recording any
+/// newly-settled meeting point against the opposite (already-closed)
+/// frontier in `best`.
+#[allow(clippy::too_many_arguments)]
+fn expand_frontier(
+    g: &Undirected2DCoordinateGraph,
+    target: NodeIndex,
+    open: &mut BinaryHeap<Reverse<(i32, NodeIndex)>>,
+    closed: &mut HashSet<NodeIndex>,
+    dist: &mut HashMap<NodeIndex, i32>,
+    came_from: &mut HashMap<NodeIndex, NodeIndex>,
+    other_closed: &HashSet<NodeIndex>,
+    other_dist: &HashMap<NodeIndex, i32>,
+    best: &mut Option<(i32, NodeIndex)>,
+    heuristic: &impl Fn(NodeIndex, NodeIndex) -> i32,
+) {
+    let Some(Reverse((_, u))) = open.pop() else {
+        return;
+    };
+    if !closed.insert(u) {
+        return;
+    }
+
+    if other_closed.contains(&u) {
+        let candidate = dist[&u] + other_dist[&u];
+        if best.is_none_or(|(cost, _)| candidate < cost) {
+            *best = Some((candidate, u));
+        }
+    }
+
+    for edge in g.edges(u) {
+        let v = edge.target();
+        let new_cost = dist[&u] + *edge.weight();
+        if new_cost < *dist.get(&v).unwrap_or(&i32::MAX) {
+            dist.insert(v, new_cost);
+            came_from.insert(v, u);
+            open.push(Reverse((new_cost + heuristic(v, target), v)));
+        }
+    }
+}
+
+fn str_to_config(str: String) -> std::io::Result<Config> {
+    let config: Config = serde_json::from_str(str.as_str()).unwrap();
+    config.validate()?;
+    Ok(config)
+}
+
+/// Body of a `POST /route` request, overriding the loaded map's default
+/// start/goal for this query only. The graph itself stays cached.
+#[derive(Default, Deserialize)]
+struct RouteRequest {
+    start: Option<Coordinate2D>,
+    goal: Option<Coordinate2D>,
+    start_id: Option<String>,
+    goal_id: Option<String>,
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap()
+}
+
+fn svg_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"image/svg+xml"[..]).unwrap()
+}
+
+fn read_route_request(request: &mut Request) -> RouteRequest {
+    let mut body = String::new();
+    let _ = request.as_reader().read_to_string(&mut body);
+    serde_json::from_str(&body).unwrap_or_default()
+}
+
+fn serve_grid(
+    g: Undirected2DCoordinateGraph,
+    config: Config,
+    algorithm: Algorithm,
+    port: u16,
+) -> std::io::Result<()> {
+    let server = Server::http(("0.0.0.0", port)).expect("failed to bind HTTP server");
+    serve_grid_requests(server, g, config, algorithm)
+}
+
+/// Request-handling loop shared by `serve_grid`, split out so tests can drive
+/// it against a `Server` bound to an OS-assigned port instead of a fixed one.
+fn serve_grid_requests(
+    server: Server,
+    g: Undirected2DCoordinateGraph,
+    config: Config,
+    algorithm: Algorithm,
+) -> std::io::Result<()> {
+    for mut request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (Method::Post, "/route") => {
+                let route_request = read_route_request(&mut request);
+                let start = route_request.start.unwrap_or(config.start);
+                let goal = route_request.goal.unwrap_or(config.goal);
+                let path = find_path(&g, start, goal, config.connectivity, algorithm);
+                Response::from_string(graph_to_json(&g, &path)).with_header(json_header())
+            }
+            (Method::Get, "/route.svg") => {
+                let path = find_path(
+                    &g,
+                    config.start,
+                    config.goal,
+                    config.connectivity,
+                    algorithm,
+                );
+                let svg = graph_to_svg(&g, config.scale, &config, config.start, config.goal, &path);
+                Response::from_string(svg).with_header(svg_header())
+            }
+            _ => Response::from_string("not found").with_status_code(404),
+        };
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+fn serve_geo(g: GeoRouteGraph, config: Config, port: u16) -> std::io::Result<()> {
+    let server = Server::http(("0.0.0.0", port)).expect("failed to bind HTTP server");
+
+    for mut request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (Method::Post, "/route") => {
+                let route_request = read_route_request(&mut request);
+                let start_id = route_request.start_id.or_else(|| config.start_id.clone());
+                let goal_id = route_request.goal_id.or_else(|| config.goal_id.clone());
+                let path = match (start_id, goal_id) {
+                    (Some(start_id), Some(goal_id)) => find_geo_path(&g, &start_id, &goal_id),
+                    _ => None,
+                };
+                Response::from_string(graph_to_json_geo(&g, &path)).with_header(json_header())
+            }
+            (Method::Get, "/route.svg") => {
+                let path = match (&config.start_id, &config.goal_id) {
+                    (Some(start_id), Some(goal_id)) => find_geo_path(&g, start_id, goal_id),
+                    _ => None,
+                };
+                Response::from_string(graph_to_svg_geo(&g, config.scale, &path))
+                    .with_header(svg_header())
+            }
+            _ => Response::from_string("not found").with_status_code(404),
+        };
+        let _ = request.respond(response);
+    }
+
+    Ok(())
 }
 
-fn str_to_config(str: String) -> Config {
-    serde_json::from_str(str.as_str()).unwrap()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::algo::dijkstra;
+
+    fn test_config(width: i16, height: i16, blocked: Vec<Coordinate2D>) -> Config {
+        Config {
+            start: Coordinate2D { x: 0, y: 0 },
+            goal: Coordinate2D {
+                x: width - 1,
+                y: height - 1,
+            },
+            height,
+            width,
+            scale: 10,
+            costs: None,
+            blocked,
+            connectivity: Connectivity::Four,
+            start_id: None,
+            goal_id: None,
+        }
+    }
+
+    fn dijkstra_cost(g: &Undirected2DCoordinateGraph, start: NodeIndex, goal: NodeIndex) -> i32 {
+        *dijkstra(g, start, Some(goal), |e| *e.weight())
+            .get(&goal)
+            .expect("goal should be reachable")
+    }
+
+    #[test]
+    fn is_blocked_matches_configured_cells() {
+        let config = test_config(3, 3, vec![Coordinate2D { x: 1, y: 1 }]);
+
+        assert!(config.is_blocked(Coordinate2D { x: 1, y: 1 }));
+        assert!(!config.is_blocked(Coordinate2D { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn cost_at_defaults_to_one_outside_the_costs_grid() {
+        let mut config = test_config(3, 3, vec![]);
+        config.costs = Some(vec![vec![1, 5, 1], vec![1, 1, 1]]);
+
+        assert_eq!(config.cost_at(Coordinate2D { x: 1, y: 0 }), 5);
+        assert_eq!(config.cost_at(Coordinate2D { x: 0, y: 2 }), 1);
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_costs() {
+        let mut config = test_config(3, 1, vec![]);
+        config.costs = Some(vec![vec![1, -5, 1]]);
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_accepts_positive_costs() {
+        let mut config = test_config(3, 1, vec![]);
+        config.costs = Some(vec![vec![1, 5, 1]]);
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn make_graph_skips_blocked_cells() {
+        let config = test_config(3, 1, vec![Coordinate2D { x: 1, y: 0 }]);
+        let g = make_graph(&config);
+
+        assert_eq!(g.node_count(), 2);
+        let left = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 0, y: 0 });
+        let right = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 2, y: 0 });
+        assert!(left.is_some());
+        assert!(right.is_some());
+        // The blocked middle cell splits the row, so the two surviving
+        // cells have no edge between them.
+        assert!(g.find_edge(left.unwrap(), right.unwrap()).is_none());
+    }
+
+    #[test]
+    fn make_graph_weights_edges_by_terrain_cost() {
+        let mut config = test_config(2, 1, vec![]);
+        config.costs = Some(vec![vec![1, 7]]);
+        let g = make_graph(&config);
+
+        let left = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 0, y: 0 })
+            .unwrap();
+        let right = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 1, y: 0 })
+            .unwrap();
+        let edge = g
+            .find_edge(left, right)
+            .expect("adjacent cells should be connected");
+
+        assert_eq!(
+            *g.edge_weight(edge).unwrap(),
+            config.cost_at(Coordinate2D { x: 1, y: 0 })
+        );
+    }
+
+    #[test]
+    fn octile_distance_matches_orthogonal_and_diagonal_costs() {
+        let origin = Coordinate2D { x: 0, y: 0 };
+
+        assert_eq!(origin.octile_distance(origin), 0);
+        assert_eq!(
+            origin.octile_distance(Coordinate2D { x: 3, y: 0 }),
+            3 * ORTHOGONAL_COST
+        );
+        assert_eq!(
+            origin.octile_distance(Coordinate2D { x: 3, y: 3 }),
+            3 * DIAGONAL_COST
+        );
+        // Mixed move: 2 diagonal steps to close the shorter axis, then 3
+        // orthogonal steps along the remainder of the longer one.
+        assert_eq!(
+            origin.octile_distance(Coordinate2D { x: 5, y: 2 }),
+            2 * DIAGONAL_COST + 3 * ORTHOGONAL_COST
+        );
+    }
+
+    #[test]
+    fn grid_heuristic_picks_octile_only_for_eight_connectivity() {
+        let a = Coordinate2D { x: 0, y: 0 };
+        let b = Coordinate2D { x: 3, y: 4 };
+
+        assert_eq!(
+            grid_heuristic(Connectivity::Four, a, b),
+            a.distance(b).floor() as i32
+        );
+        assert_eq!(
+            grid_heuristic(Connectivity::Eight, a, b),
+            a.octile_distance(b)
+        );
+    }
+
+    #[test]
+    fn make_graph_adds_diagonal_edges_for_eight_connectivity() {
+        let mut config = test_config(2, 2, vec![]);
+        config.connectivity = Connectivity::Eight;
+        let g = make_graph(&config);
+
+        let top_left = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 0, y: 0 })
+            .unwrap();
+        let bottom_right = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 1, y: 1 })
+            .unwrap();
+        let edge = g
+            .find_edge(top_left, bottom_right)
+            .expect("diagonal neighbors should be connected under Eight connectivity");
+
+        assert_eq!(*g.edge_weight(edge).unwrap(), DIAGONAL_COST);
+    }
+
+    #[test]
+    fn make_graph_has_no_diagonal_edges_for_four_connectivity() {
+        let config = test_config(2, 2, vec![]);
+        let g = make_graph(&config);
+
+        let top_left = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 0, y: 0 })
+            .unwrap();
+        let bottom_right = g
+            .node_indices()
+            .find(|x| g[*x] == Coordinate2D { x: 1, y: 1 })
+            .unwrap();
+
+        assert!(g.find_edge(top_left, bottom_right).is_none());
+    }
+
+    fn geo_coordinate(id: &str, lat: f64, lon: f64) -> GeoCoordinate {
+        GeoCoordinate {
+            id: id.to_string(),
+            name: id.to_string(),
+            lat,
+            lon,
+        }
+    }
+
+    #[test]
+    fn haversine_distance_is_zero_for_the_same_point() {
+        let london = geo_coordinate("london", 51.5074, -0.1278);
+
+        assert_eq!(london.haversine_distance(&london), 0.0);
+    }
+
+    #[test]
+    fn haversine_distance_matches_known_great_circle_distance() {
+        let london = geo_coordinate("london", 51.5074, -0.1278);
+        let paris = geo_coordinate("paris", 48.8566, 2.3522);
+
+        // Known great-circle distance is ~344 km; allow a few km of slack
+        // for the coordinates' limited precision.
+        let distance = london.haversine_distance(&paris);
+        assert!(
+            (340.0..348.0).contains(&distance),
+            "expected ~344km, got {}",
+            distance
+        );
+    }
+
+    #[test]
+    fn find_geo_path_routes_through_intermediate_nodes() {
+        let nodes = vec![
+            geo_coordinate("a", 0.0, 0.0),
+            geo_coordinate("b", 0.0, 1.0),
+            geo_coordinate("c", 0.0, 2.0),
+        ];
+        let edges = vec![
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "c".to_string()),
+        ];
+        let g = make_geo_graph(&nodes, &edges);
+
+        let (distance, path) = find_geo_path(&g, "a", "c").expect("path should be found");
+        let ids: Vec<&str> = path.iter().map(|idx| g[*idx].id.as_str()).collect();
+
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(
+            distance,
+            nodes[0].haversine_distance(&nodes[1]) + nodes[1].haversine_distance(&nodes[2])
+        );
+    }
+
+    #[test]
+    fn find_geo_path_returns_none_for_unknown_ids() {
+        let nodes = vec![geo_coordinate("a", 0.0, 0.0)];
+        let g = make_geo_graph(&nodes, &[]);
+
+        assert!(find_geo_path(&g, "a", "missing").is_none());
+    }
+
+    #[test]
+    fn serve_grid_responds_to_route_requests() {
+        use std::io::{Read, Write};
+        use std::net::TcpStream;
+
+        let config = test_config(3, 3, vec![]);
+        let g = make_graph(&config);
+        let server = Server::http("127.0.0.1:0").expect("failed to bind test server");
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .expect("test server should have an IP address");
+        std::thread::spawn(move || {
+            serve_grid_requests(server, g, config, Algorithm::AStar).unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).expect("failed to connect to test server");
+        stream
+            .write_all(b"GET /route.svg HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("<svg"));
+    }
+
+    #[test]
+    fn bidirectional_astar_matches_dijkstra_on_open_grid() {
+        let config = test_config(5, 5, vec![]);
+        let g = make_graph(&config);
+        let start = g.node_indices().find(|x| g[*x] == config.start).unwrap();
+        let goal = g.node_indices().find(|x| g[*x] == config.goal).unwrap();
+
+        let (cost, path) = bidirectional_astar(&g, start, goal, |a, b| {
+            grid_heuristic(config.connectivity, g[a], g[b])
+        })
+        .expect("path should be found");
+
+        assert_eq!(cost, dijkstra_cost(&g, start, goal));
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+    }
+
+    #[test]
+    fn bidirectional_astar_matches_dijkstra_with_obstacles() {
+        let blocked = vec![
+            Coordinate2D { x: 2, y: 0 },
+            Coordinate2D { x: 2, y: 1 },
+            Coordinate2D { x: 2, y: 3 },
+            Coordinate2D { x: 2, y: 4 },
+        ];
+        let config = test_config(5, 5, blocked);
+        let g = make_graph(&config);
+        let start = g.node_indices().find(|x| g[*x] == config.start).unwrap();
+        let goal = g.node_indices().find(|x| g[*x] == config.goal).unwrap();
+
+        let (cost, _) = bidirectional_astar(&g, start, goal, |a, b| {
+            grid_heuristic(config.connectivity, g[a], g[b])
+        })
+        .expect("path should be found");
+
+        assert_eq!(cost, dijkstra_cost(&g, start, goal));
+    }
+
+    #[test]
+    fn align_paths_classifies_inserted_and_removed_nodes() {
+        let before = vec![
+            Coordinate2D { x: 0, y: 0 },
+            Coordinate2D { x: 1, y: 0 },
+            Coordinate2D { x: 2, y: 0 },
+        ];
+        let after = vec![
+            Coordinate2D { x: 0, y: 0 },
+            Coordinate2D { x: 1, y: 1 },
+            Coordinate2D { x: 2, y: 0 },
+        ];
+
+        let alignment = align_paths(&before, &after);
+        let statuses: Vec<DiffStatus> = alignment.iter().map(|e| e.status).collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                DiffStatus::Unchanged,
+                DiffStatus::Removed,
+                DiffStatus::Added,
+                DiffStatus::Unchanged,
+            ]
+        );
+        assert_eq!(alignment[0].node, before[0]);
+        assert_eq!(alignment[1].node, before[1]);
+        assert_eq!(alignment[2].node, after[1]);
+        assert_eq!(alignment[3].node, before[2]);
+    }
 }